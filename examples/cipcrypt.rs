@@ -12,3 +12,22 @@ pub unsafe extern "C" fn ipcrypt_decrypt(v: u32, key: *const u8) -> u32 {
 	let key = ::std::mem::transmute::<*const u8, &ipcrypt::Key>(key);
 	ipcrypt::decrypt(v, key)
 }
+
+/// Returns `true` and writes the derived key to `out_key` on success, or
+/// `false` (leaving `out_key` untouched) if `secret` is not valid UTF-8.
+#[cfg(feature = "passphrase")]
+#[no_mangle]
+pub unsafe extern "C" fn ipcrypt_key_from_passphrase(
+	secret: *const u8,
+	secret_len: usize,
+	out_key: *mut u8,
+) -> bool {
+	let secret = ::std::slice::from_raw_parts(secret, secret_len);
+	let secret = match ::std::str::from_utf8(secret) {
+		Ok(secret) => secret,
+		Err(_) => return false,
+	};
+	let key = ipcrypt::key_from_passphrase(secret);
+	::std::ptr::copy_nonoverlapping(key.as_ptr(), out_key, key.len());
+	true
+}
@@ -15,6 +15,15 @@
 //! This crate supports a `no-std` feature which removes support for
 //! `Ipv4Addr` (because it's not available in `core`).
 //!
+//! A `passphrase` feature adds [`key_from_passphrase`]/
+//! [`key_from_passphrase_salted`], deriving a [`Key`] from an
+//! arbitrary-length secret string via a KDF; it is incompatible with
+//! `no-std` since it needs a hash.
+//!
+//! [`key_from_passphrase`]: fn.key_from_passphrase.html
+//! [`key_from_passphrase_salted`]: fn.key_from_passphrase_salted.html
+//! [`Key`]: type.Key.html
+//!
 //! # Example
 //!
 //! ```
@@ -30,14 +39,39 @@
 extern crate core;
 
 #[cfg(not(feature = "no-std"))]
-use std::net::Ipv4Addr;
+extern crate rand;
 
+#[cfg(not(feature = "no-std"))]
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+#[cfg(not(feature = "no-std"))]
+use rand::rngs::OsRng;
+#[cfg(not(feature = "no-std"))]
+use rand::RngCore;
+
+use core::assert_eq;
 use core::convert::{From, Into};
+use core::iter::Iterator;
+use core::marker::Copy;
 use core::ops::BitXorAssign;
 
+mod aes;
+
+use aes::Aes128;
+
+#[cfg(feature = "passphrase")]
+mod passphrase;
+
+#[cfg(feature = "passphrase")]
+pub use passphrase::{key_from_passphrase, key_from_passphrase_salted};
+
 /// Alias for the key type (16 bytes)
 pub type Key = [u8; 16];
 
+/// Alias for the key type used by `encrypt_ndx`/`decrypt_ndx` (32 bytes:
+/// two concatenated 16-byte AES-128 keys).
+pub type KeyX = [u8; 32];
+
 /// The inner state permutations are build on.  Input and Output types
 /// are converted to an from this type.
 ///
@@ -51,8 +85,19 @@ pub type Key = [u8; 16];
 pub struct State(u8, u8, u8, u8);
 
 impl State {
-	fn encrypt(mut self, key: &Key) -> Self {
-		let KeyStates(a, b, c, d) = KeyStates::from(key);
+	fn encrypt(self, key: &Key) -> Self {
+		self.encrypt_with(&KeyStates::from(key))
+	}
+
+	fn decrypt(self, key: &Key) -> Self {
+		self.decrypt_with(&KeyStates::from(key))
+	}
+
+	// Same as `encrypt`, but takes an already expanded key schedule so
+	// batch callers only pay for `KeyStates::from` once per buffer
+	// instead of once per value.
+	fn encrypt_with(mut self, ks: &KeyStates) -> Self {
+		let KeyStates(a, b, c, d) = *ks;
 
 		self ^= a;
 		self = self.permute();
@@ -65,8 +110,8 @@ impl State {
 		self
 	}
 
-	fn decrypt(mut self, key: &Key) -> Self {
-		let KeyStates(a, b, c, d) = KeyStates::from(key);
+	fn decrypt_with(mut self, ks: &KeyStates) -> Self {
+		let KeyStates(a, b, c, d) = *ks;
 
 		self ^= d;
 		self = self.permute_inverse();
@@ -229,10 +274,450 @@ where
 	State::from(v).decrypt(key).into()
 }
 
+/// Encrypt every element of `input` into `output`, expanding the key
+/// schedule only once for the whole buffer instead of once per element
+/// like calling [`encrypt`] in a loop would.
+///
+/// # Panics
+///
+/// Panics if `input` and `output` have different lengths.
+///
+/// [`encrypt`]: fn.encrypt.html
+pub fn encrypt_slice<T>(input: &[T], key: &Key, output: &mut [T])
+where
+	T: Copy,
+	State: From<T> + Into<T>,
+{
+	assert_eq!(input.len(), output.len());
+	let ks = KeyStates::from(key);
+	for (i, o) in input.iter().zip(output.iter_mut()) {
+		*o = State::from(*i).encrypt_with(&ks).into();
+	}
+}
+
+/// Decrypt every element of `input` into `output`, expanding the key
+/// schedule only once for the whole buffer.
+///
+/// # Panics
+///
+/// Panics if `input` and `output` have different lengths.
+pub fn decrypt_slice<T>(input: &[T], key: &Key, output: &mut [T])
+where
+	T: Copy,
+	State: From<T> + Into<T>,
+{
+	assert_eq!(input.len(), output.len());
+	let ks = KeyStates::from(key);
+	for (i, o) in input.iter().zip(output.iter_mut()) {
+		*o = State::from(*i).decrypt_with(&ks).into();
+	}
+}
+
+/// Encrypt every element of `values` in place, expanding the key schedule
+/// only once for the whole buffer.
+pub fn encrypt_in_place<T>(values: &mut [T], key: &Key)
+where
+	T: Copy,
+	State: From<T> + Into<T>,
+{
+	let ks = KeyStates::from(key);
+	for v in values.iter_mut() {
+		*v = State::from(*v).encrypt_with(&ks).into();
+	}
+}
+
+/// Decrypt every element of `values` in place, expanding the key schedule
+/// only once for the whole buffer.
+pub fn decrypt_in_place<T>(values: &mut [T], key: &Key)
+where
+	T: Copy,
+	State: From<T> + Into<T>,
+{
+	let ks = KeyStates::from(key);
+	for v in values.iter_mut() {
+		*v = State::from(*v).decrypt_with(&ks).into();
+	}
+}
+
+/// The 128-bit state `encrypt_deterministic`/`decrypt_deterministic` are
+/// built on.  Input and Output types are converted to and from this type.
+///
+/// Unlike [`State`], which is permuted with a small hand-rolled cipher,
+/// `State128` is encrypted with a single AES-128 block operation, which
+/// makes it a much stronger (but also much more expensive) primitive.
+///
+/// [`State`]: struct.State.html
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct State128([u8; 16]);
+
+impl State128 {
+	fn encrypt_deterministic(self, key: &Key) -> Self {
+		let mut block = self.0;
+		Aes128::new(key).encrypt_block(&mut block);
+		State128(block)
+	}
+
+	fn decrypt_deterministic(self, key: &Key) -> Self {
+		let mut block = self.0;
+		Aes128::new(key).decrypt_block(&mut block);
+		State128(block)
+	}
+}
+
+impl From<[u8; 16]> for State128 {
+	#[inline(always)]
+	fn from(v: [u8; 16]) -> Self {
+		State128(v)
+	}
+}
+
+impl Into<[u8; 16]> for State128 {
+	#[inline(always)]
+	fn into(self) -> [u8; 16] {
+		self.0
+	}
+}
+
+impl From<u128> for State128 {
+	#[inline(always)]
+	fn from(v: u128) -> Self {
+		State128(v.to_be_bytes())
+	}
+}
+
+impl Into<u128> for State128 {
+	#[inline(always)]
+	fn into(self) -> u128 {
+		u128::from_be_bytes(self.0)
+	}
+}
+
+#[cfg(not(feature = "no-std"))]
+impl From<Ipv6Addr> for State128 {
+	#[inline(always)]
+	fn from(ip: Ipv6Addr) -> Self {
+		State128(ip.octets())
+	}
+}
+
+#[cfg(not(feature = "no-std"))]
+impl Into<Ipv6Addr> for State128 {
+	#[inline(always)]
+	fn into(self) -> Ipv6Addr {
+		self.0.into()
+	}
+}
+
+/// IPv4 addresses are embedded as `::ffff:a.b.c.d` so the 128-bit modes
+/// can handle both address families through one API; the result of
+/// encrypting one is a full [`Ipv6Addr`], since the ciphertext is not in
+/// general still a mapped IPv4 address.
+///
+/// [`Ipv6Addr`]: https://doc.rust-lang.org/std/net/struct.Ipv6Addr.html
+#[cfg(not(feature = "no-std"))]
+impl From<Ipv4Addr> for State128 {
+	#[inline(always)]
+	fn from(ip: Ipv4Addr) -> Self {
+		State128(ip.to_ipv6_mapped().octets())
+	}
+}
+
+/// Encrypt a 128-bit value (or an [`Ipv4Addr`] embedded as `::ffff:a.b.c.d`)
+/// with given key, using the `ipcrypt-deterministic` AES-128 construction.
+///
+/// # Example
+///
+/// ```
+/// use std::net::Ipv6Addr;
+/// let addr = "2001:db8::1".parse::<Ipv6Addr>().unwrap();
+/// println!("{}", ipcrypt::encrypt_deterministic(addr, b"0123456789abcdef"));
+/// ```
+///
+/// [`Ipv4Addr`]: https://doc.rust-lang.org/std/net/struct.Ipv4Addr.html
+#[cfg(not(feature = "no-std"))]
+pub fn encrypt_deterministic<T>(v: T, key: &Key) -> Ipv6Addr
+where
+	State128: From<T>,
+{
+	State128::from(v).encrypt_deterministic(key).into()
+}
+
+/// Decrypt a value produced by [`encrypt_deterministic`] with the same key.
+///
+/// [`encrypt_deterministic`]: fn.encrypt_deterministic.html
+#[cfg(not(feature = "no-std"))]
+pub fn decrypt_deterministic(v: Ipv6Addr, key: &Key) -> Ipv6Addr {
+	State128::from(v).decrypt_deterministic(key).into()
+}
+
+/// Encrypt a raw 16-byte value with given key, using the
+/// `ipcrypt-deterministic` AES-128 construction.
+pub fn encrypt_deterministic_raw(v: [u8; 16], key: &Key) -> [u8; 16] {
+	State128::from(v).encrypt_deterministic(key).into()
+}
+
+/// Decrypt a value produced by [`encrypt_deterministic_raw`] with the same
+/// key.
+///
+/// [`encrypt_deterministic_raw`]: fn.encrypt_deterministic_raw.html
+pub fn decrypt_deterministic_raw(v: [u8; 16], key: &Key) -> [u8; 16] {
+	State128::from(v).decrypt_deterministic(key).into()
+}
+
+/// Encrypt every element of `values` in place with given key, expanding
+/// the AES-128 key schedule only once for the whole buffer.
+pub fn encrypt_deterministic_raw_in_place(values: &mut [[u8; 16]], key: &Key) {
+	let cipher = Aes128::new(key);
+	for v in values.iter_mut() {
+		cipher.encrypt_block(v);
+	}
+}
+
+/// Decrypt every element of `values` in place, as produced by
+/// [`encrypt_deterministic_raw_in_place`] with the same key.
+///
+/// [`encrypt_deterministic_raw_in_place`]: fn.encrypt_deterministic_raw_in_place.html
+pub fn decrypt_deterministic_raw_in_place(values: &mut [[u8; 16]], key: &Key) {
+	let cipher = Aes128::new(key);
+	for v in values.iter_mut() {
+		cipher.decrypt_block(v);
+	}
+}
+
+// Lays an 8-byte `ipcrypt-nd` tweak out as the 16-byte KIASU-BC tweak
+// block: the tweak bytes fill the top two rows of the (column-major)
+// AES state, the bottom two rows stay zero.
+fn nd_tweak_block(tweak: &[u8; 8]) -> [u8; 16] {
+	let mut t = [0u8; 16];
+	t[0] = tweak[0];
+	t[1] = tweak[1];
+	t[4] = tweak[2];
+	t[5] = tweak[3];
+	t[8] = tweak[4];
+	t[9] = tweak[5];
+	t[12] = tweak[6];
+	t[13] = tweak[7];
+	t
+}
+
+/// Encrypt a 128-bit value (or an [`Ipv4Addr`] embedded as `::ffff:a.b.c.d`)
+/// with given key, using the non-deterministic `ipcrypt-nd` construction
+/// (KIASU-BC tweaked with a fresh random 8-byte tweak on every call).
+///
+/// Unlike [`encrypt_deterministic`], equal inputs do not produce equal
+/// outputs, which avoids leaking which addresses repeat in the input.
+/// The returned 24 bytes are `tweak(8) || ciphertext(16)`.
+///
+/// [`encrypt_deterministic`]: fn.encrypt_deterministic.html
+/// [`Ipv4Addr`]: https://doc.rust-lang.org/std/net/struct.Ipv4Addr.html
+#[cfg(not(feature = "no-std"))]
+pub fn encrypt_nd<T>(v: T, key: &Key) -> [u8; 24]
+where
+	State128: From<T>,
+{
+	nd_encrypt_block(&Aes128::new(key), State128::from(v).into())
+}
+
+/// Decrypt a value produced by [`encrypt_nd`] with the same key, returning
+/// the raw 16-byte address (convert with e.g. `Ipv6Addr::from` as needed).
+///
+/// [`encrypt_nd`]: fn.encrypt_nd.html
+#[cfg(not(feature = "no-std"))]
+pub fn decrypt_nd(v: &[u8; 24], key: &Key) -> [u8; 16] {
+	nd_decrypt_block(&Aes128::new(key), v)
+}
+
+#[cfg(not(feature = "no-std"))]
+fn nd_encrypt_block(cipher: &Aes128, plain: [u8; 16]) -> [u8; 24] {
+	let mut tweak = [0u8; 8];
+	OsRng.fill_bytes(&mut tweak);
+
+	let mut block = plain;
+	cipher.encrypt_block_tweaked(&mut block, &nd_tweak_block(&tweak));
+
+	let mut out = [0u8; 24];
+	out[..8].copy_from_slice(&tweak);
+	out[8..].copy_from_slice(&block);
+	out
+}
+
+#[cfg(not(feature = "no-std"))]
+fn nd_decrypt_block(cipher: &Aes128, v: &[u8; 24]) -> [u8; 16] {
+	let mut tweak = [0u8; 8];
+	tweak.copy_from_slice(&v[..8]);
+
+	let mut block = [0u8; 16];
+	block.copy_from_slice(&v[8..]);
+	cipher.decrypt_block_tweaked(&mut block, &nd_tweak_block(&tweak));
+	block
+}
+
+/// Like [`encrypt_nd`], but expands the AES-128 key schedule once and
+/// reuses it for every element of `input`, instead of once per value.
+///
+/// # Panics
+///
+/// Panics if `input` and `output` have different lengths.
+///
+/// [`encrypt_nd`]: fn.encrypt_nd.html
+#[cfg(not(feature = "no-std"))]
+pub fn encrypt_nd_slice<T>(input: &[T], key: &Key, output: &mut [[u8; 24]])
+where
+	T: Copy,
+	State128: From<T>,
+{
+	assert_eq!(input.len(), output.len());
+	let cipher = Aes128::new(key);
+	for (i, o) in input.iter().zip(output.iter_mut()) {
+		*o = nd_encrypt_block(&cipher, State128::from(*i).into());
+	}
+}
+
+/// Like [`decrypt_nd`], but expands the AES-128 key schedule once and
+/// reuses it for every element of `input`.
+///
+/// # Panics
+///
+/// Panics if `input` and `output` have different lengths.
+///
+/// [`decrypt_nd`]: fn.decrypt_nd.html
+#[cfg(not(feature = "no-std"))]
+pub fn decrypt_nd_slice(input: &[[u8; 24]], key: &Key, output: &mut [[u8; 16]]) {
+	assert_eq!(input.len(), output.len());
+	let cipher = Aes128::new(key);
+	for (i, o) in input.iter().zip(output.iter_mut()) {
+		*o = nd_decrypt_block(&cipher, i);
+	}
+}
+
+fn split_keyx(key: &KeyX) -> (Key, Key) {
+	let mut k1 = [0u8; 16];
+	let mut k2 = [0u8; 16];
+	k1.copy_from_slice(&key[..16]);
+	k2.copy_from_slice(&key[16..]);
+	(k1, k2)
+}
+
+/// Encrypt a 128-bit value (or an [`Ipv4Addr`] embedded as `::ffff:a.b.c.d`)
+/// with given 32-byte key, using the extended-tweak `ipcrypt-ndx`
+/// construction: a standard XEX mode built from two plain AES-128
+/// encryptions, giving a full 16-byte random tweak instead of the 8 bytes
+/// [`encrypt_nd`] uses.
+///
+/// The returned 32 bytes are `tweak(16) || ciphertext(16)`.
+///
+/// [`encrypt_nd`]: fn.encrypt_nd.html
+/// [`Ipv4Addr`]: https://doc.rust-lang.org/std/net/struct.Ipv4Addr.html
+#[cfg(not(feature = "no-std"))]
+pub fn encrypt_ndx<T>(v: T, key: &KeyX) -> [u8; 32]
+where
+	State128: From<T>,
+{
+	let (cipher1, cipher2) = ndx_ciphers(key);
+	ndx_encrypt_block(&cipher1, &cipher2, State128::from(v).into())
+}
+
+/// Decrypt a value produced by [`encrypt_ndx`] with the same key, returning
+/// the raw 16-byte address.
+///
+/// [`encrypt_ndx`]: fn.encrypt_ndx.html
+#[cfg(not(feature = "no-std"))]
+pub fn decrypt_ndx(v: &[u8; 32], key: &KeyX) -> [u8; 16] {
+	let (cipher1, cipher2) = ndx_ciphers(key);
+	ndx_decrypt_block(&cipher1, &cipher2, v)
+}
+
+#[cfg(not(feature = "no-std"))]
+fn ndx_ciphers(key: &KeyX) -> (Aes128, Aes128) {
+	let (k1, k2) = split_keyx(key);
+	(Aes128::new(&k1), Aes128::new(&k2))
+}
+
+#[cfg(not(feature = "no-std"))]
+fn ndx_encrypt_block(cipher1: &Aes128, cipher2: &Aes128, plain: [u8; 16]) -> [u8; 32] {
+	let mut tweak = [0u8; 16];
+	OsRng.fill_bytes(&mut tweak);
+
+	let mut et = tweak;
+	cipher2.encrypt_block(&mut et);
+
+	let mut block = plain;
+	for i in 0..16 {
+		block[i] ^= et[i];
+	}
+	cipher1.encrypt_block(&mut block);
+	for i in 0..16 {
+		block[i] ^= et[i];
+	}
+
+	let mut out = [0u8; 32];
+	out[..16].copy_from_slice(&tweak);
+	out[16..].copy_from_slice(&block);
+	out
+}
+
+#[cfg(not(feature = "no-std"))]
+fn ndx_decrypt_block(cipher1: &Aes128, cipher2: &Aes128, v: &[u8; 32]) -> [u8; 16] {
+	let mut tweak = [0u8; 16];
+	tweak.copy_from_slice(&v[..16]);
+
+	let mut et = tweak;
+	cipher2.encrypt_block(&mut et);
+
+	let mut block = [0u8; 16];
+	block.copy_from_slice(&v[16..]);
+	for i in 0..16 {
+		block[i] ^= et[i];
+	}
+	cipher1.decrypt_block(&mut block);
+	for i in 0..16 {
+		block[i] ^= et[i];
+	}
+	block
+}
+
+/// Like [`encrypt_ndx`], but expands both AES-128 key schedules once and
+/// reuses them for every element of `input`.
+///
+/// # Panics
+///
+/// Panics if `input` and `output` have different lengths.
+///
+/// [`encrypt_ndx`]: fn.encrypt_ndx.html
+#[cfg(not(feature = "no-std"))]
+pub fn encrypt_ndx_slice<T>(input: &[T], key: &KeyX, output: &mut [[u8; 32]])
+where
+	T: Copy,
+	State128: From<T>,
+{
+	assert_eq!(input.len(), output.len());
+	let (cipher1, cipher2) = ndx_ciphers(key);
+	for (i, o) in input.iter().zip(output.iter_mut()) {
+		*o = ndx_encrypt_block(&cipher1, &cipher2, State128::from(*i).into());
+	}
+}
+
+/// Like [`decrypt_ndx`], but expands both AES-128 key schedules once and
+/// reuses them for every element of `input`.
+///
+/// # Panics
+///
+/// Panics if `input` and `output` have different lengths.
+///
+/// [`decrypt_ndx`]: fn.decrypt_ndx.html
+#[cfg(not(feature = "no-std"))]
+pub fn decrypt_ndx_slice(input: &[[u8; 32]], key: &KeyX, output: &mut [[u8; 16]]) {
+	assert_eq!(input.len(), output.len());
+	let (cipher1, cipher2) = ndx_ciphers(key);
+	for (i, o) in input.iter().zip(output.iter_mut()) {
+		*o = ndx_decrypt_block(&cipher1, &cipher2, i);
+	}
+}
+
 #[cfg(test)]
 #[cfg(not(feature = "no-std"))]
 mod test {
-	use {decrypt, encrypt, Key};
+	use {core::assert_eq, decrypt, encrypt, Key};
 	use std::net::Ipv4Addr;
 
 	fn check_addr(key: &Key, plain: Ipv4Addr, cipher: Ipv4Addr) {
@@ -267,7 +752,7 @@ mod test {
 
 #[cfg(test)]
 mod test_raw {
-	use {decrypt, encrypt, Key};
+	use {core::assert_eq, decrypt, encrypt, Key};
 
 	fn check(key: &Key, plain: [u8; 4], cipher: [u8; 4]) {
 		assert_eq!(encrypt(plain, key), cipher);
@@ -292,3 +777,193 @@ mod test_raw {
 		check(KEY, [1, 2, 3, 4], [171, 238, 15, 199]);
 	}
 }
+
+#[cfg(test)]
+mod test_slice {
+	use {core::assert_eq, decrypt_in_place, decrypt_slice, encrypt_in_place, encrypt_slice, Key};
+
+	static KEY: &Key = b"some 16-byte key";
+
+	const PLAIN: [[u8; 4]; 3] = [[127, 0, 0, 1], [8, 8, 8, 8], [1, 2, 3, 4]];
+	const CIPHER: [[u8; 4]; 3] = [
+		[114, 62, 227, 59],
+		[46, 48, 51, 50],
+		[171, 238, 15, 199],
+	];
+
+	#[test]
+	fn test_encrypt_slice() {
+		let mut out = [[0u8; 4]; 3];
+		encrypt_slice(&PLAIN, KEY, &mut out);
+		assert_eq!(out, CIPHER);
+	}
+
+	#[test]
+	fn test_decrypt_slice() {
+		let mut out = [[0u8; 4]; 3];
+		decrypt_slice(&CIPHER, KEY, &mut out);
+		assert_eq!(out, PLAIN);
+	}
+
+	#[test]
+	fn test_in_place_roundtrip() {
+		let mut values = PLAIN;
+		encrypt_in_place(&mut values, KEY);
+		assert_eq!(values, CIPHER);
+		decrypt_in_place(&mut values, KEY);
+		assert_eq!(values, PLAIN);
+	}
+}
+
+#[cfg(test)]
+mod test_128 {
+	use {
+		core::assert_eq, core::assert_ne, decrypt_deterministic_raw,
+		decrypt_deterministic_raw_in_place, encrypt_deterministic_raw,
+		encrypt_deterministic_raw_in_place, Key,
+	};
+
+	fn check(key: &Key, plain: [u8; 16]) {
+		let cipher = encrypt_deterministic_raw(plain, key);
+		assert_ne!(cipher, plain);
+		assert_eq!(decrypt_deterministic_raw(cipher, key), plain);
+	}
+
+	static KEY: &Key = b"some 16-byte key";
+
+	#[test]
+	fn test_zero() {
+		check(KEY, [0; 16]);
+	}
+
+	#[test]
+	fn test_sequential() {
+		check(KEY, [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16]);
+	}
+
+	#[test]
+	fn test_ones() {
+		check(KEY, [0xff; 16]);
+	}
+
+	#[test]
+	fn test_in_place_roundtrip() {
+		let mut values = [
+			[0; 16],
+			[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16],
+		];
+		let plain = values;
+		encrypt_deterministic_raw_in_place(&mut values, KEY);
+		assert_ne!(values, plain);
+		decrypt_deterministic_raw_in_place(&mut values, KEY);
+		assert_eq!(values, plain);
+	}
+}
+
+#[cfg(test)]
+#[cfg(not(feature = "no-std"))]
+mod test_nd {
+	use {
+		core::assert_eq, core::assert_ne, decrypt_nd, decrypt_nd_slice, encrypt_nd,
+		encrypt_nd_slice, Key,
+	};
+
+	static KEY: &Key = b"some 16-byte key";
+
+	#[test]
+	fn test_roundtrip() {
+		let plain = [
+			1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16,
+		];
+		let cipher = encrypt_nd(plain, KEY);
+		assert_eq!(decrypt_nd(&cipher, KEY), plain);
+	}
+
+	#[test]
+	fn test_tweak_randomizes_output() {
+		let plain = [0u8; 16];
+		let a = encrypt_nd(plain, KEY);
+		let b = encrypt_nd(plain, KEY);
+		assert_ne!(a, b);
+	}
+
+	#[test]
+	fn test_slice_roundtrip() {
+		let plain = [[0u8; 16], [0xff; 16]];
+		let mut cipher = [[0u8; 24]; 2];
+		encrypt_nd_slice(&plain, KEY, &mut cipher);
+
+		let mut out = [[0u8; 16]; 2];
+		decrypt_nd_slice(&cipher, KEY, &mut out);
+		assert_eq!(out, plain);
+	}
+}
+
+#[cfg(test)]
+#[cfg(not(feature = "no-std"))]
+mod test_ndx {
+	use {
+		core::assert_eq, core::assert_ne, decrypt_ndx, decrypt_ndx_slice, encrypt_ndx,
+		encrypt_ndx_slice, KeyX,
+	};
+
+	static KEY: &KeyX = b"some 16-byte key+another 16 byt.";
+
+	#[test]
+	fn test_roundtrip() {
+		let plain = [
+			1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16,
+		];
+		let cipher = encrypt_ndx(plain, KEY);
+		assert_eq!(decrypt_ndx(&cipher, KEY), plain);
+	}
+
+	#[test]
+	fn test_tweak_randomizes_output() {
+		let plain = [0u8; 16];
+		let a = encrypt_ndx(plain, KEY);
+		let b = encrypt_ndx(plain, KEY);
+		assert_ne!(a, b);
+	}
+
+	#[test]
+	fn test_slice_roundtrip() {
+		let plain = [[0u8; 16], [0xff; 16]];
+		let mut cipher = [[0u8; 32]; 2];
+		encrypt_ndx_slice(&plain, KEY, &mut cipher);
+
+		let mut out = [[0u8; 16]; 2];
+		decrypt_ndx_slice(&cipher, KEY, &mut out);
+		assert_eq!(out, plain);
+	}
+}
+
+#[cfg(test)]
+#[cfg(feature = "passphrase")]
+mod test_passphrase {
+	use {core::assert_eq, core::assert_ne, key_from_passphrase, key_from_passphrase_salted};
+
+	#[test]
+	fn test_deterministic() {
+		assert_eq!(
+			key_from_passphrase("correct horse battery staple"),
+			key_from_passphrase("correct horse battery staple")
+		);
+	}
+
+	#[test]
+	fn test_different_passphrases_differ() {
+		assert_ne!(
+			key_from_passphrase("correct horse battery staple"),
+			key_from_passphrase("Tr0ub4dor&3")
+		);
+	}
+
+	#[test]
+	fn test_salt_changes_key() {
+		assert_ne!(
+			key_from_passphrase_salted("correct horse battery staple", b"salt-a"),
+			key_from_passphrase_salted("correct horse battery staple", b"salt-b")
+		);
+	}
+}
@@ -0,0 +1,159 @@
+//! Deriving a [`Key`] from an arbitrary-length passphrase instead of a
+//! fixed 16-byte secret, for the shared-secret configuration pattern used
+//! by VPN-style tools (every party configured with the same passphrase
+//! ends up with the same key).
+//!
+//! Requires the `passphrase` feature, since it needs a hash to build the
+//! KDF from and is therefore not available in `no-std` builds.
+
+use {core::convert::From, core::iter::Iterator, Key, std::vec::Vec};
+
+const SHA256_IV: [u32; 8] = [
+	0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+const SHA256_K: [u32; 64] = [
+	0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+	0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+	0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+	0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+	0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+	0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+	0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+	0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+fn sha256(msg: &[u8]) -> [u8; 32] {
+	let mut h = SHA256_IV;
+
+	let mut data = Vec::from(msg);
+	let bit_len = (msg.len() as u64) * 8;
+	data.push(0x80);
+	while data.len() % 64 != 56 {
+		data.push(0);
+	}
+	data.extend_from_slice(&bit_len.to_be_bytes());
+
+	for block in data.chunks(64) {
+		sha256_process_block(&mut h, block);
+	}
+
+	let mut out = [0u8; 32];
+	for (i, word) in h.iter().enumerate() {
+		out[4 * i..4 * i + 4].copy_from_slice(&word.to_be_bytes());
+	}
+	out
+}
+
+fn sha256_process_block(h: &mut [u32; 8], block: &[u8]) {
+	let mut w = [0u32; 64];
+	for (i, word) in w.iter_mut().take(16).enumerate() {
+		*word = u32::from_be_bytes([
+			block[4 * i],
+			block[4 * i + 1],
+			block[4 * i + 2],
+			block[4 * i + 3],
+		]);
+	}
+	for i in 16..64 {
+		let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+		let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+		w[i] = w[i - 16]
+			.wrapping_add(s0)
+			.wrapping_add(w[i - 7])
+			.wrapping_add(s1);
+	}
+
+	let (mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut hh) =
+		(h[0], h[1], h[2], h[3], h[4], h[5], h[6], h[7]);
+
+	for i in 0..64 {
+		let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+		let ch = (e & f) ^ ((!e) & g);
+		let temp1 = hh
+			.wrapping_add(s1)
+			.wrapping_add(ch)
+			.wrapping_add(SHA256_K[i])
+			.wrapping_add(w[i]);
+		let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+		let maj = (a & b) ^ (a & c) ^ (b & c);
+		let temp2 = s0.wrapping_add(maj);
+
+		hh = g;
+		g = f;
+		f = e;
+		e = d.wrapping_add(temp1);
+		d = c;
+		c = b;
+		b = a;
+		a = temp1.wrapping_add(temp2);
+	}
+
+	h[0] = h[0].wrapping_add(a);
+	h[1] = h[1].wrapping_add(b);
+	h[2] = h[2].wrapping_add(c);
+	h[3] = h[3].wrapping_add(d);
+	h[4] = h[4].wrapping_add(e);
+	h[5] = h[5].wrapping_add(f);
+	h[6] = h[6].wrapping_add(g);
+	h[7] = h[7].wrapping_add(hh);
+}
+
+fn hmac_sha256(key: &[u8], msg: &[u8]) -> [u8; 32] {
+	let mut block_key = [0u8; 64];
+	if key.len() > 64 {
+		block_key[..32].copy_from_slice(&sha256(key));
+	} else {
+		block_key[..key.len()].copy_from_slice(key);
+	}
+
+	let mut ipad = [0x36u8; 64];
+	let mut opad = [0x5cu8; 64];
+	for i in 0..64 {
+		ipad[i] ^= block_key[i];
+		opad[i] ^= block_key[i];
+	}
+
+	let mut inner = Vec::from(&ipad[..]);
+	inner.extend_from_slice(msg);
+	let inner_hash = sha256(&inner);
+
+	let mut outer = Vec::from(&opad[..]);
+	outer.extend_from_slice(&inner_hash);
+	sha256(&outer)
+}
+
+// HKDF (RFC 5869) over HMAC-SHA256, truncated to the 16 bytes a `Key`
+// needs: `extract` turns the passphrase into a uniform pseudorandom key,
+// `expand` (a single round, since 16 bytes fit in one HMAC output) derives
+// the key material from it.
+fn derive_key(secret: &[u8], salt: &[u8]) -> Key {
+	let prk = hmac_sha256(salt, secret);
+
+	let mut info = Vec::from(&b"ipcrypt-rs passphrase key v1"[..]);
+	info.push(0x01);
+	let okm = hmac_sha256(&prk, &info);
+
+	let mut key = [0u8; 16];
+	key.copy_from_slice(&okm[..16]);
+	key
+}
+
+/// Derive a [`Key`] from a passphrase, without a salt.
+///
+/// Multiple parties configured with the same passphrase derive the same
+/// key, which is convenient for config files and CLIs compared to
+/// shipping a raw 16-byte key around.
+///
+/// [`Key`]: type.Key.html
+pub fn key_from_passphrase(secret: &str) -> Key {
+	derive_key(secret.as_bytes(), &[])
+}
+
+/// Like [`key_from_passphrase`], but additionally mixes in `salt`, so the
+/// same passphrase used with different salts yields unrelated keys.
+///
+/// [`key_from_passphrase`]: fn.key_from_passphrase.html
+pub fn key_from_passphrase_salted(secret: &str, salt: &[u8]) -> Key {
+	derive_key(secret.as_bytes(), salt)
+}